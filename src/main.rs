@@ -2,9 +2,27 @@ pub mod oidc;
 pub mod utils;
 
 use authify::oidc::callback_listener;
-use authify::oidc::oidc_client::OidcClient;
+use authify::oidc::oidc_client::{ClientAuthentication, OidcClient, TokenEndpointResponse};
+use authify::oidc::token_cache::{self, CachedToken};
+use authify::utils::{self, DisplayOptions, OutputFormat, DEFAULT_MAX_COLUMN_WIDTH};
 use clap::Parser;
-use serde_json::to_string_pretty;
+
+/// Client authentication scheme to use at the token endpoint, overriding the
+/// auto-detection `OidcClient` otherwise performs from the provider's
+/// well-known configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ClientAuthMethod {
+    /// Auto-detect from the provider's well-known configuration (default).
+    Auto,
+    /// No client credentials (public client).
+    None,
+    /// `client_secret_post`: the secret travels in the token request body.
+    ClientSecretPost,
+    /// `client_secret_basic`: the secret travels in an HTTP Basic header.
+    ClientSecretBasic,
+    /// `private_key_jwt`: sign a JWT assertion with `--signing-key`/`--signing-alg`.
+    PrivateKeyJwt,
+}
 
 #[derive(Parser, Debug)]
 #[command(version, about = "Authify: An OIDC client CLI tool to login and get access tokens", long_about = None)]
@@ -18,9 +36,9 @@ pub struct Args {
     #[arg(short = 'c', long)]
     pub client_id: String,
 
-    /// The client secret registered with the OIDC provider
+    /// The client secret registered with the OIDC provider (omit for public clients)
     #[arg(short = 's', long)]
-    pub client_secret: String,
+    pub client_secret: Option<String>,
 
     /// The redirect URL for the OIDC provider (default: http://127.0.0.1:3030/callback)
     #[arg(short = 'r', long, default_value = "http://127.0.0.1:3030/callback")]
@@ -37,82 +55,243 @@ pub struct Args {
     /// An optional refresh token to renew the access token
     #[arg(short = 'f', long)]
     pub refresh_token: Option<String>,
+
+    /// Use the OAuth 2.0 Device Authorization Grant instead of opening a browser
+    #[arg(short = 'd', long)]
+    pub device: bool,
+
+    /// Delete the cached token for this issuer/client and revoke it if possible
+    #[arg(long, alias = "clear")]
+    pub logout: bool,
+
+    /// How to render JSON results: table, json, csv, or yaml
+    #[arg(short = 'O', long, value_enum, default_value = "table")]
+    pub output: OutputFormat,
+
+    /// An RFC 6901 JSON Pointer selecting a sub-tree of the result to display
+    #[arg(short = 'S', long)]
+    pub select: Option<String>,
+
+    /// Column width (in characters) at which table values are truncated or wrapped
+    #[arg(long, default_value_t = DEFAULT_MAX_COLUMN_WIDTH)]
+    pub max_width: usize,
+
+    /// Wrap long table values across continuation rows instead of truncating them
+    #[arg(long)]
+    pub wrap: bool,
+
+    /// Override the client authentication scheme instead of auto-detecting it
+    #[arg(long, value_enum, default_value = "auto")]
+    pub client_auth_method: ClientAuthMethod,
+
+    /// Path to a PEM-encoded private key; required when --client-auth-method=private-key-jwt
+    #[arg(long)]
+    pub signing_key: Option<String>,
+
+    /// JWS algorithm for the private_key_jwt assertion
+    #[arg(long, default_value = "RS256")]
+    pub signing_alg: String,
 }
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Args = Args::parse();
 
     let mut client = OidcClient::new(
         &args.issuer,
         &args.client_id,
-        &args.client_secret,
+        args.client_secret.as_deref(),
         &args.redirect_url,
         args.scope.split_whitespace().map(String::from).collect(),
         args.state,
-    )?;
+    )
+    .await?;
 
+    apply_client_auth_override(&mut client, &args)?;
+
+    let display = DisplayOptions {
+        output: args.output,
+        select: args.select.clone(),
+        max_width: args.max_width,
+        wrap: args.wrap,
+    };
     let wells = client.get_well_knowns();
-    println!("Well-Known Endpoints: {}", to_string_pretty(&wells)?);
+    println!("Well-Known Endpoints:");
+    utils::display_json_result(&wells, &display.without_select());
+
+    if args.logout {
+        return handle_logout(&client).await;
+    }
 
     if let Some(refresh_token) = &args.refresh_token {
-        handle_refresh_token(&mut client, refresh_token)?;
+        handle_refresh_token(&mut client, refresh_token, &display).await?;
+        return Ok(());
+    }
+
+    if let Some(cached) = token_cache::load(client.issuer(), client.client_id()) {
+        let now = token_cache::now()?;
+        if cached.is_valid(now) {
+            println!("Using cached access token");
+            return show_user_info(&client, &cached.access_token, &display).await;
+        }
+
+        if let Some(refresh_token) = cached.refresh_token.clone() {
+            if let Ok(token_endpoint_response) = client.refresh_token(&refresh_token).await {
+                println!("Refreshed cached access token");
+                cache_token_response(&client, &token_endpoint_response)?;
+                return show_user_info(&client, &token_endpoint_response.access_token, &display).await;
+            }
+        }
+    }
+
+    if args.device {
+        handle_device_flow(&mut client, &display).await?;
     } else {
-        handle_authorization_code_flow(&mut client)?;
+        handle_authorization_code_flow(&mut client, &display).await?;
     }
 
     Ok(())
 }
 
-fn handle_refresh_token(
+/// Applies an explicit `--client-auth-method` override over the scheme
+/// `OidcClient::new` auto-detected, if one was requested.
+fn apply_client_auth_override(
     client: &mut OidcClient,
-    refresh_token: &str,
+    args: &Args,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let token_endpoint_response = client.refresh_token(refresh_token)?;
-    println!(
-        "Token Endpoint Response: {}",
-        to_string_pretty(&token_endpoint_response)?
-    );
+    match args.client_auth_method {
+        ClientAuthMethod::Auto => {}
+        ClientAuthMethod::None => client.set_client_authentication(ClientAuthentication::None),
+        ClientAuthMethod::ClientSecretPost => {
+            let client_secret = args
+                .client_secret
+                .clone()
+                .ok_or("--client-auth-method=client-secret-post requires --client-secret")?;
+            client.set_client_authentication(ClientAuthentication::ClientSecretPost { client_secret });
+        }
+        ClientAuthMethod::ClientSecretBasic => {
+            let client_secret = args
+                .client_secret
+                .clone()
+                .ok_or("--client-auth-method=client-secret-basic requires --client-secret")?;
+            client.set_client_authentication(ClientAuthentication::ClientSecretBasic { client_secret });
+        }
+        ClientAuthMethod::PrivateKeyJwt => {
+            let signing_key_path = args
+                .signing_key
+                .as_ref()
+                .ok_or("--client-auth-method=private-key-jwt requires --signing-key")?;
+            let signing_key = std::fs::read_to_string(signing_key_path)?;
+            client.set_client_authentication(ClientAuthentication::PrivateKeyJwt {
+                signing_key: signing_key.into(),
+                alg: args.signing_alg.clone(),
+            });
+        }
+    }
+    Ok(())
+}
 
-    if let Ok(user_info) = client.get_user_info(&token_endpoint_response.access_token) {
-        println!("User Info: {}", to_string_pretty(&user_info)?);
+fn cache_token_response(
+    client: &OidcClient,
+    response: &TokenEndpointResponse,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cached = CachedToken::from_token_response(response, token_cache::now()?);
+    token_cache::store(client.issuer(), client.client_id(), &cached)?;
+    Ok(())
+}
+
+async fn show_user_info(
+    client: &OidcClient,
+    access_token: &str,
+    display: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Ok(user_info) = client.get_user_info(access_token).await {
+        println!("User Info:");
+        utils::display_json_result(&user_info, &display.without_select());
     } else {
         eprintln!("Failed to get user info");
     }
     Ok(())
 }
 
-fn handle_authorization_code_flow(
+async fn handle_logout(client: &OidcClient) -> Result<(), Box<dyn std::error::Error>> {
+    match token_cache::clear(client.issuer(), client.client_id())? {
+        Some(cached) => {
+            if client.get_well_knowns().revocation_endpoint().is_some() {
+                if let Err(e) = client.revoke_token(&cached.access_token).await {
+                    eprintln!("Failed to revoke token: {}", e);
+                }
+            }
+            println!("Logged out and cleared cached credentials");
+        }
+        None => println!("No cached credentials to clear"),
+    }
+    Ok(())
+}
+
+async fn handle_refresh_token(
     client: &mut OidcClient,
+    refresh_token: &str,
+    display: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let token_endpoint_response = client.refresh_token(refresh_token).await?;
+    println!("Token Endpoint Response:");
+    utils::display_json_result(&token_endpoint_response, display);
+    cache_token_response(client, &token_endpoint_response)?;
+
+    show_user_info(client, &token_endpoint_response.access_token, display).await
+}
+
+async fn handle_device_flow(
+    client: &mut OidcClient,
+    display: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let device_authorization = client.start_device_authorization().await?;
+
+    println!(
+        "To sign in, visit {} and enter code: {}",
+        device_authorization.verification_uri, device_authorization.user_code
+    );
+    if let Some(uri) = &device_authorization.verification_uri_complete {
+        println!("Or open this link directly: {}", uri);
+    }
+
+    let token_endpoint_response = client.poll_device_token(&device_authorization).await?;
+    println!("Token Endpoint Response:");
+    utils::display_json_result(&token_endpoint_response, display);
+    cache_token_response(client, &token_endpoint_response)?;
+
+    show_user_info(client, &token_endpoint_response.access_token, display).await
+}
+
+async fn handle_authorization_code_flow(
+    client: &mut OidcClient,
+    display: &DisplayOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let auth_url = client.build_authorization_url()?;
     println!("Authorization URL: {}", auth_url.clone());
 
-    if open::that(auth_url).is_err() {
+    if open::that(&auth_url).is_err() {
         eprintln!("Failed to open auth URL in browser");
     }
 
-    let runtime = tokio::runtime::Runtime::new().unwrap();
-    let code = runtime.block_on(async {
-        match callback_listener::listen().await {
-            Ok(code) => Ok(code),
-            Err(e) => {
-                eprintln!("Failed to get auth code: {}", e);
-                Err(e as Box<dyn std::error::Error>)
-            }
+    let expected_state = client
+        .state()
+        .ok_or("Authorization URL was not built; no state to validate the callback against")?
+        .to_string();
+
+    let (code, _state) = match callback_listener::listen(expected_state).await {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Failed to get auth code: {}", e);
+            return Err(e);
         }
-    })?;
+    };
     println!("Authorization code: {}", code.clone());
 
-    let token_endpoint_response = client.get_token(code.as_str())?;
-    println!(
-        "Token Endpoint Response: {}",
-        to_string_pretty(&token_endpoint_response)?
-    );
-
-    if let Ok(user_info) = client.get_user_info(&token_endpoint_response.access_token) {
-        println!("User Info: {}", to_string_pretty(&user_info)?);
-    } else {
-        eprintln!("Failed to get user info");
-    }
+    let token_endpoint_response = client.get_token(code.as_str()).await?;
+    println!("Token Endpoint Response:");
+    utils::display_json_result(&token_endpoint_response, display);
+    cache_token_response(client, &token_endpoint_response)?;
 
-    Ok(())
+    show_user_info(client, &token_endpoint_response.access_token, display).await
 }
@@ -3,9 +3,16 @@ use std::sync::Arc;
 use tokio::sync::{oneshot, Mutex};
 use warp::Filter;
 
-pub async fn listen() -> Result<String, Box<dyn std::error::Error>> {
-    // Create oneshot channels for authorization code and server shutdown
-    let (code_tx, code_rx) = oneshot::channel::<String>();
+type CallbackResult = Result<(String, String), String>;
+
+/// Runs the local redirect listener and waits for the provider's callback,
+/// rejecting any response whose `state` doesn't match `expected_state`
+/// (CSRF/authorization-code-injection protection) and surfacing any
+/// `error`/`error_description` the provider returns instead of a code.
+/// Returns the authorization `code` and the echoed `state` on success.
+pub async fn listen(expected_state: String) -> Result<(String, String), Box<dyn std::error::Error>> {
+    // Create oneshot channels for the callback result and server shutdown
+    let (code_tx, code_rx) = oneshot::channel::<CallbackResult>();
     let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
 
     // Wrap the senders in Arc<Mutex<>> to share across threads
@@ -14,8 +21,14 @@ pub async fn listen() -> Result<String, Box<dyn std::error::Error>> {
 
     // Define the callback route
     let callback_route = warp::path("callback")
-        .and(warp::query::<std::collections::HashMap<String, String>>())
-        .and(warp::any().map(move || (Arc::clone(&code_tx), Arc::clone(&shutdown_tx))))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::any().map(move || {
+            (
+                Arc::clone(&code_tx),
+                Arc::clone(&shutdown_tx),
+                expected_state.clone(),
+            )
+        }))
         .and_then(handle_callback);
 
     // Start the server with a graceful shutdown signal
@@ -29,11 +42,15 @@ pub async fn listen() -> Result<String, Box<dyn std::error::Error>> {
     // Run the server in a separate async task
     tokio::spawn(server);
 
-    // Wait for the authorization code from the oneshot channel
+    // Wait for the callback result from the oneshot channel
     match code_rx.await {
-        Ok(code) => {
+        Ok(Ok((code, state))) => {
             println!("Server closed. Authorization code: {}", code);
-            Ok(code)
+            Ok((code, state))
+        }
+        Ok(Err(e)) => {
+            eprintln!("Authorization callback rejected: {}", e);
+            Err(e.into())
         }
         Err(e) => {
             eprintln!("Server closed without receiving an authorization code.");
@@ -45,29 +62,45 @@ pub async fn listen() -> Result<String, Box<dyn std::error::Error>> {
 async fn handle_callback(
     params: HashMap<String, String>,
     channels: (
-        Arc<Mutex<Option<oneshot::Sender<String>>>>,
+        Arc<Mutex<Option<oneshot::Sender<CallbackResult>>>>,
         Arc<Mutex<Option<oneshot::Sender<()>>>>,
+        String,
     ),
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let (code_tx, shutdown_tx) = channels;
-
-    if let Some(code) = params.get("code") {
-        println!("Authorization code received: {}", code);
+    let (code_tx, shutdown_tx, expected_state) = channels;
 
-        // Send the code and shutdown signal
-        if let Some(tx) = code_tx.lock().await.take() {
-            let _ = tx.send(code.to_string());
-        }
-        if let Some(tx) = shutdown_tx.lock().await.take() {
-            let _ = tx.send(());
+    let result: CallbackResult = if let Some(error) = params.get("error") {
+        let description = params
+            .get("error_description")
+            .cloned()
+            .unwrap_or_default();
+        Err(format!("Provider returned error: {} ({})", error, description))
+    } else {
+        match (params.get("code"), params.get("state")) {
+            (Some(code), Some(state)) if *state == expected_state => {
+                Ok((code.clone(), state.clone()))
+            }
+            (Some(_), Some(_)) => Err("State mismatch in callback; possible CSRF".to_string()),
+            (Some(_), None) => Err("Callback is missing the state parameter".to_string()),
+            (None, _) => Err("No authorization code found in the query".to_string()),
         }
+    };
 
-        Ok(warp::reply::html(
-            "Authorization code received. You can close this window.",
-        ))
+    if let Err(e) = &result {
+        eprintln!("Authorization callback rejected: {}", e);
     } else {
-        Ok(warp::reply::html(
-            "No authorization code found in the query.",
-        ))
+        println!("Authorization code received");
     }
+
+    if let Some(tx) = code_tx.lock().await.take() {
+        let _ = tx.send(result.clone());
+    }
+    if let Some(tx) = shutdown_tx.lock().await.take() {
+        let _ = tx.send(());
+    }
+
+    Ok(match result {
+        Ok(_) => warp::reply::html("Authorization code received. You can close this window.".to_string()),
+        Err(e) => warp::reply::html(format!("Authorization failed: {}", e)),
+    })
 }
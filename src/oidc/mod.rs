@@ -0,0 +1,4 @@
+pub mod callback_listener;
+pub mod jwt_client;
+pub mod oidc_client;
+pub mod token_cache;
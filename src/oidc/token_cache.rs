@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::oidc::jwt_client::OidcError;
+use crate::oidc::oidc_client::TokenEndpointResponse;
+
+/// A cached token-endpoint response, with the relative `expires_in` resolved
+/// to an absolute Unix timestamp so validity survives across invocations.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CachedToken {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_at: u64,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+    pub id_token: Option<String>,
+}
+
+impl CachedToken {
+    pub fn from_token_response(response: &TokenEndpointResponse, fetched_at: u64) -> Self {
+        Self {
+            access_token: response.access_token.clone(),
+            token_type: response.token_type.clone(),
+            expires_at: fetched_at + response.expires_in,
+            refresh_token: response.refresh_token.clone(),
+            scope: response.scope.clone(),
+            id_token: response.id_token.clone(),
+        }
+    }
+
+    pub fn is_valid(&self, now: u64) -> bool {
+        now < self.expires_at
+    }
+}
+
+type TokenCacheMap = HashMap<String, CachedToken>;
+
+pub fn now() -> Result<u64, OidcError> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|e| OidcError::InvalidResponse(e.to_string()))
+}
+
+fn cache_key(issuer: &str, client_id: &str) -> String {
+    format!("{}::{}", issuer, client_id)
+}
+
+fn cache_file_path() -> Result<PathBuf, OidcError> {
+    let mut dir = dirs::config_dir().ok_or_else(|| {
+        OidcError::InvalidResponse("Could not determine user config directory".to_string())
+    })?;
+    dir.push("authify");
+    Ok(dir.join("token_cache.json"))
+}
+
+/// Reads the cache file leniently: it's a hand-editable local file (users may
+/// trim or comment out an entry by hand), so `//`/`/* */` comments and
+/// trailing commas are tolerated via `parse_lenient` rather than requiring
+/// strict JSON.
+fn read_cache_map(path: &PathBuf) -> TokenCacheMap {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| crate::utils::parse_lenient(&contents).ok())
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn write_cache_map(path: &PathBuf, map: &TokenCacheMap) -> Result<(), OidcError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| OidcError::InvalidResponse(e.to_string()))?;
+    }
+
+    let serialized = serde_json::to_string_pretty(map)
+        .map_err(|e| OidcError::InvalidResponse(e.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| OidcError::InvalidResponse(e.to_string()))?;
+        file.write_all(serialized.as_bytes())
+            .map_err(|e| OidcError::InvalidResponse(e.to_string()))?;
+    }
+
+    #[cfg(not(unix))]
+    {
+        std::fs::write(path, serialized).map_err(|e| OidcError::InvalidResponse(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Loads the cached token for a given issuer/client_id pair, if any.
+pub fn load(issuer: &str, client_id: &str) -> Option<CachedToken> {
+    let path = cache_file_path().ok()?;
+    read_cache_map(&path).remove(&cache_key(issuer, client_id))
+}
+
+/// Persists (or overwrites) the cached token for a given issuer/client_id pair.
+pub fn store(issuer: &str, client_id: &str, token: &CachedToken) -> Result<(), OidcError> {
+    let path = cache_file_path()?;
+    let mut map = read_cache_map(&path);
+    map.insert(cache_key(issuer, client_id), token.clone());
+    write_cache_map(&path, &map)
+}
+
+/// Removes and returns the cached token for a given issuer/client_id pair.
+pub fn clear(issuer: &str, client_id: &str) -> Result<Option<CachedToken>, OidcError> {
+    let path = cache_file_path()?;
+    let mut map = read_cache_map(&path);
+    let removed = map.remove(&cache_key(issuer, client_id));
+    write_cache_map(&path, &map)?;
+    Ok(removed)
+}
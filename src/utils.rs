@@ -1,8 +1,145 @@
 use prettytable::{row, Cell, Row, Table};
 use serde::Serialize;
 use serde_json::{Map, Value};
+use std::io::IsTerminal;
 
-const MAX_COLUMN_WIDTH: usize = 80;
+/// Default column width (in characters) for table output; see `--max-width`.
+pub const DEFAULT_MAX_COLUMN_WIDTH: usize = 80;
+
+/// Output format selectable via the CLI's `--output`/`-O` flag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-friendly prettytable rendering (the default).
+    Table,
+    /// Pretty-printed raw JSON, for piping into `jq` or similar.
+    Json,
+    /// One nesting level flattened into CSV columns.
+    Csv,
+    /// YAML, for config-style consumption.
+    Yaml,
+}
+
+/// Renders a `serde_json::Value` in a specific output format. Implementations
+/// all work off the same `Value` the display layer builds, so a new format
+/// only has to translate that one model rather than re-deriving it.
+pub trait Formatter {
+    fn format(&self, value: &Value) -> String;
+}
+
+pub struct TableFormatter {
+    max_width: usize,
+    wrap: bool,
+}
+
+impl TableFormatter {
+    pub fn new(max_width: usize, wrap: bool) -> Self {
+        Self { max_width, wrap }
+    }
+}
+
+pub struct JsonFormatter;
+pub struct CsvFormatter;
+pub struct YamlFormatter;
+
+impl Formatter for TableFormatter {
+    fn format(&self, value: &Value) -> String {
+        match value {
+            Value::Object(map) => render_nested_structure(map, 0, self.max_width, self.wrap),
+            other => format_value(other),
+        }
+    }
+}
+
+impl Formatter for JsonFormatter {
+    fn format(&self, value: &Value) -> String {
+        serde_json::to_string_pretty(value).unwrap_or_else(|e| format!("Error rendering JSON: {}", e))
+    }
+}
+
+impl Formatter for CsvFormatter {
+    fn format(&self, value: &Value) -> String {
+        match value {
+            Value::Object(map) => Self::format_object_rows(&[map]),
+            Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+                let rows: Vec<&Map<String, Value>> = items.iter().filter_map(Value::as_object).collect();
+                Self::format_object_rows(&rows)
+            }
+            // An array of scalars (e.g. a selected `amr` or `roles` claim) has
+            // no columns to derive headers from; emit one value per row
+            // instead of silently dropping it the way the object-rows path would.
+            Value::Array(items) => {
+                let mut writer = csv::Writer::from_writer(vec![]);
+                for item in items {
+                    let _ = writer.write_record([format_value(item)]);
+                }
+                String::from_utf8(writer.into_inner().unwrap_or_default()).unwrap_or_default()
+            }
+            other => format_value(other),
+        }
+    }
+}
+
+impl CsvFormatter {
+    fn format_object_rows(rows: &[&Map<String, Value>]) -> String {
+        if rows.is_empty() {
+            return String::new();
+        }
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        let headers: Vec<&str> = rows[0].keys().map(String::as_str).collect();
+        let _ = writer.write_record(&headers);
+        for row in rows {
+            let fields: Vec<String> = headers
+                .iter()
+                .map(|key| row.get(*key).map(format_value).unwrap_or_default())
+                .collect();
+            let _ = writer.write_record(&fields);
+        }
+
+        String::from_utf8(writer.into_inner().unwrap_or_default()).unwrap_or_default()
+    }
+}
+
+impl Formatter for YamlFormatter {
+    fn format(&self, value: &Value) -> String {
+        serde_yaml::to_string(value).unwrap_or_else(|e| format!("Error rendering YAML: {}", e))
+    }
+}
+
+fn formatter_for(options: &DisplayOptions) -> Box<dyn Formatter> {
+    match options.output {
+        OutputFormat::Table => Box::new(TableFormatter::new(options.max_width, options.wrap)),
+        OutputFormat::Json => Box::new(JsonFormatter),
+        OutputFormat::Csv => Box::new(CsvFormatter),
+        OutputFormat::Yaml => Box::new(YamlFormatter),
+    }
+}
+
+/// Options controlling how a result is displayed, threaded through the CLI's
+/// display-related flags (`--output`, `--select`, ...).
+#[derive(Clone, Debug)]
+pub struct DisplayOptions {
+    pub output: OutputFormat,
+    /// An RFC 6901 JSON Pointer selecting a sub-tree of the result to render.
+    pub select: Option<String>,
+    /// Column width (in characters) at which table values are truncated or
+    /// wrapped; only applies to `OutputFormat::Table`.
+    pub max_width: usize,
+    /// Wrap long table values across continuation rows instead of truncating.
+    pub wrap: bool,
+}
+
+impl DisplayOptions {
+    /// Same formatting options with `select` cleared, for displaying a result
+    /// other than the one `--select` was written to project (e.g. well-knowns
+    /// or user-info, when `--select` targets the token response).
+    pub fn without_select(&self) -> Self {
+        Self {
+            select: None,
+            ..self.clone()
+        }
+    }
+}
 
 pub fn display_request_parameters(params: &[(impl AsRef<str>, impl AsRef<str>)]) {
     let mut table = Table::new();
@@ -16,34 +153,133 @@ pub fn display_request_parameters(params: &[(impl AsRef<str>, impl AsRef<str>)])
     table.printstd();
 }
 
-pub fn display_json_result<T: Serialize>(value: &T) {
+pub fn display_json_result<T: Serialize>(value: &T, options: &DisplayOptions) {
     let json_value = serde_json::to_value(value).expect("Failed to serialize data");
-    match json_value {
-        Value::Object(map) => display_nested_structure(&map, 0),
-        _ => println!("Data is not a JSON object."),
+
+    let target = match &options.select {
+        Some(pointer) => match resolve_json_pointer(&json_value, pointer) {
+            Some(selected) => selected.clone(),
+            None => {
+                println!("Path not found: {}", pointer);
+                return;
+            }
+        },
+        None => json_value,
+    };
+
+    println!("{}", formatter_for(options).format(&target));
+}
+
+/// Resolves an RFC 6901 JSON Pointer against `root`. An empty pointer selects
+/// the whole document; `~1` and `~0` in reference tokens decode to `/` and
+/// `~` respectively, and numeric tokens index into arrays.
+fn resolve_json_pointer<'a>(root: &'a Value, pointer: &str) -> Option<&'a Value> {
+    if pointer.is_empty() {
+        return Some(root);
     }
+    if !pointer.starts_with('/') {
+        return None;
+    }
+
+    let mut current = root;
+    for token in pointer[1..].split('/') {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        current = match current {
+            Value::Object(map) => map.get(&token)?,
+            Value::Array(arr) => arr.get(token.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
 }
 
-fn display_nested_structure(map: &Map<String, Value>, indent: usize) {
+fn render_nested_structure(map: &Map<String, Value>, indent: usize, max_width: usize, wrap: bool) -> String {
     let mut table = Table::new();
+    add_object_rows(&mut table, map, indent, max_width, wrap);
+    table.to_string()
+}
+
+fn add_object_rows(table: &mut Table, map: &Map<String, Value>, indent: usize, max_width: usize, wrap: bool) {
     for (key, value) in map {
-        let formatted_key = format!("{}{}", " ".repeat(indent), key);
-        let formatted_value = format_value(value);
-        for (i, line) in formatted_value.lines().enumerate() {
-            if i == 0 {
-                table.add_row(Row::new(vec![
-                    Cell::new(&formatted_key),
-                    Cell::new(&truncate_line(line)),
-                ]));
-            } else {
-                table.add_row(Row::new(vec![
-                    Cell::new(""),
-                    Cell::new(&truncate_line(line)),
-                ]));
+        add_value_row(table, key, value, indent, max_width, wrap);
+    }
+}
+
+/// Adds a row for `key: value`, recursing into nested objects as indented
+/// sub-rows and expanding arrays of objects into enumerated `key[0]`,
+/// `key[1]` sub-rows instead of collapsing them to `"{...}"`.
+fn add_value_row(
+    table: &mut Table,
+    key: &str,
+    value: &Value,
+    indent: usize,
+    max_width: usize,
+    wrap: bool,
+) {
+    let formatted_key = format!("{}{}", "  ".repeat(indent), key);
+    let colored_key = colorize_key(&formatted_key);
+    match value {
+        Value::Object(map) => {
+            table.add_row(Row::new(vec![Cell::new(&colored_key), Cell::new("")]));
+            add_object_rows(table, map, indent + 1, max_width, wrap);
+        }
+        Value::Array(items) if items.iter().any(Value::is_object) => {
+            table.add_row(Row::new(vec![Cell::new(&colored_key), Cell::new("")]));
+            for (i, item) in items.iter().enumerate() {
+                add_value_row(table, &format!("{}[{}]", key, i), item, indent + 1, max_width, wrap);
+            }
+        }
+        _ => {
+            let formatted_value = format_value(value);
+            let color_code = value_color_code(value);
+            let mut first_row = true;
+            for line in formatted_value.lines() {
+                let segments = if wrap {
+                    wrap_line(line, max_width)
+                } else {
+                    vec![truncate_line(line, max_width)]
+                };
+                for segment in segments {
+                    let rendered = colorize(&segment, color_code);
+                    if first_row {
+                        table.add_row(Row::new(vec![Cell::new(&colored_key), Cell::new(&rendered)]));
+                        first_row = false;
+                    } else {
+                        table.add_row(Row::new(vec![Cell::new(""), Cell::new(&rendered)]));
+                    }
+                }
             }
         }
     }
-    table.printstd();
+}
+
+/// The ANSI color code for a scalar `Value` variant, if any (arrays and
+/// objects aren't colorized here since they're rendered structurally).
+fn value_color_code(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::String(_) => Some("32"),
+        Value::Number(_) => Some("33"),
+        Value::Bool(_) => Some("35"),
+        Value::Null => Some("90"),
+        _ => None,
+    }
+}
+
+/// Whether to emit ANSI color codes: disabled by the `NO_COLOR` convention
+/// or automatically when stdout isn't a terminal, so piped output stays clean.
+fn color_enabled() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal()
+}
+
+fn colorize(text: &str, code: Option<&str>) -> String {
+    match code {
+        Some(code) if color_enabled() => format!("\x1b[{}m{}\x1b[0m", code, text),
+        _ => text.to_string(),
+    }
+}
+
+fn colorize_key(key: &str) -> String {
+    colorize(key, Some("1;36"))
 }
 
 fn format_value(value: &Value) -> String {
@@ -61,10 +297,238 @@ fn format_value(value: &Value) -> String {
     }
 }
 
-fn truncate_line(line: &str) -> String {
-    if line.len() > MAX_COLUMN_WIDTH {
-        format!("{}...", &line[..MAX_COLUMN_WIDTH])
+/// Truncates `line` to `max_width` characters (not bytes, so multibyte UTF-8
+/// content like international claims or base64url fragments can't land a
+/// slice on a non-char boundary and panic), appending `...` when cut.
+fn truncate_line(line: &str, max_width: usize) -> String {
+    if line.chars().count() > max_width {
+        let truncated: String = line.chars().take(max_width).collect();
+        format!("{}...", truncated)
     } else {
         line.to_string()
     }
 }
+
+#[cfg(test)]
+mod truncate_line_tests {
+    use super::*;
+
+    #[test]
+    fn does_not_panic_on_multibyte_boundary() {
+        // Each "é" is 2 bytes in UTF-8, so a naive byte-slice at width 3
+        // would previously land mid-character and panic.
+        let line = "ééééé";
+        assert_eq!(truncate_line(line, 3), "ééé...");
+    }
+
+    #[test]
+    fn leaves_short_lines_untouched() {
+        assert_eq!(truncate_line("hi", 10), "hi");
+    }
+}
+
+/// Splits `line` into `max_width`-character chunks instead of truncating, so
+/// a long value is fully readable across continuation rows.
+fn wrap_line(line: &str, max_width: usize) -> Vec<String> {
+    if max_width == 0 {
+        return vec![line.to_string()];
+    }
+
+    let chars: Vec<char> = line.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+
+    chars
+        .chunks(max_width)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+/// Parses a relaxed JSON dialect: `//` line comments, `/* ... */` block
+/// comments, and trailing commas before a closing `}`/`]` are all tolerated.
+/// Strips them (respecting string literals, so a `//` inside a quoted value
+/// is left untouched) and feeds the result to `serde_json`. Lets hand-edited
+/// config files and saved request bodies round-trip without surprising a
+/// user with a parse error on a stray trailing comma.
+pub fn parse_lenient(input: &str) -> Result<Value, serde_json::Error> {
+    let without_comments = strip_comments(input);
+    let without_trailing_commas = strip_trailing_commas(&without_comments);
+    serde_json::from_str(&without_trailing_commas)
+}
+
+fn strip_comments(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                output.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.next();
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod json_pointer_tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn resolves_nested_object_path() {
+        let root = json!({"a": {"b": {"c": 42}}});
+        assert_eq!(resolve_json_pointer(&root, "/a/b/c"), Some(&json!(42)));
+    }
+
+    #[test]
+    fn decodes_tilde_escapes_in_order() {
+        let root = json!({"a/b": 1, "c~d": 2});
+        assert_eq!(resolve_json_pointer(&root, "/a~1b"), Some(&json!(1)));
+        assert_eq!(resolve_json_pointer(&root, "/c~0d"), Some(&json!(2)));
+    }
+
+    #[test]
+    fn indexes_into_arrays() {
+        let root = json!({"items": ["first", "second", "third"]});
+        assert_eq!(
+            resolve_json_pointer(&root, "/items/1"),
+            Some(&json!("second"))
+        );
+    }
+
+    #[test]
+    fn empty_pointer_selects_whole_document() {
+        let root = json!({"a": 1});
+        assert_eq!(resolve_json_pointer(&root, ""), Some(&root));
+    }
+
+    #[test]
+    fn missing_path_returns_none() {
+        let root = json!({"a": {"b": 1}});
+        assert_eq!(resolve_json_pointer(&root, "/a/missing"), None);
+        assert_eq!(resolve_json_pointer(&root, "/items/5"), None);
+    }
+}
+
+fn strip_trailing_commas(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut output = String::with_capacity(input.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if in_string {
+            output.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        if c == '"' {
+            in_string = true;
+            output.push(c);
+            i += 1;
+            continue;
+        }
+
+        if c == ',' {
+            let mut lookahead = i + 1;
+            while lookahead < chars.len() && chars[lookahead].is_whitespace() {
+                lookahead += 1;
+            }
+            if matches!(chars.get(lookahead), Some('}') | Some(']')) {
+                i += 1;
+                continue;
+            }
+        }
+
+        output.push(c);
+        i += 1;
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod lenient_json_tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn double_slash_inside_a_string_is_preserved() {
+        let input = r#"{"url": "https://example.com"}"#;
+        assert_eq!(strip_comments(input), input);
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_dropped_to_end_of_input() {
+        let input = r#"{"a": 1, /* oops never closed"#;
+        assert_eq!(strip_comments(input), r#"{"a": 1, "#);
+    }
+
+    #[test]
+    fn nested_trailing_commas_are_removed() {
+        let input = r#"{"a": [1, 2,], "b": {"c": 3,},}"#;
+        assert_eq!(
+            strip_trailing_commas(input),
+            r#"{"a": [1, 2], "b": {"c": 3}}"#
+        );
+    }
+
+    #[test]
+    fn parse_lenient_round_trips_commented_and_trailing_comma_input() {
+        let input = r#"{
+            // a comment
+            "a": 1,
+            "b": [1, 2, 3,], /* trailing */
+        }"#;
+        assert_eq!(parse_lenient(input).unwrap(), json!({"a": 1, "b": [1, 2, 3]}));
+    }
+}